@@ -0,0 +1,35 @@
+//! On-demand square wave synth, driven entirely by `AudioCallback` so the device is opened
+//! once and simply toggled on/off in step with `sound_timer`, rather than pre-rendering a
+//! fixed buffer of samples.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use sdl2::audio::AudioCallback;
+
+/// Square wave generator fed directly to SDL's audio callback. `beeping` is shared with the
+/// emulator thread so the tone can be silenced without stopping/restarting the device.
+pub struct SquareWave {
+    pub phase: f32,
+    pub freq: f32,
+    pub sample_rate: f32,
+    pub volume: i16,
+    pub beeping: Arc<AtomicBool>,
+}
+
+impl AudioCallback for SquareWave {
+    type Channel = i16;
+
+    fn callback(&mut self, out: &mut [i16]) {
+        if !self.beeping.load(Ordering::Relaxed) {
+            for sample in out.iter_mut() {
+                *sample = 0;
+            }
+            return;
+        }
+
+        for sample in out.iter_mut() {
+            *sample = if self.phase < 0.5 { self.volume } else { -self.volume };
+            self.phase = (self.phase + self.freq / self.sample_rate) % 1.0;
+        }
+    }
+}
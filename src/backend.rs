@@ -0,0 +1,51 @@
+//! Traits that decouple the CPU core from any particular frontend, so `Chip8` can be driven
+//! headlessly (for tests, or a non-SDL frontend) as well as by the real SDL window.
+
+/// Receives the 64x32 monochrome framebuffer once per frame.
+pub trait VideoBackend {
+    fn draw(&mut self, screen: &[[bool; 64]; 32]);
+}
+
+/// Turns the CHIP-8 beep on or off. Called once per frame with the current state of
+/// `sound_timer > 0`.
+pub trait AudioBackend {
+    fn set_beep(&mut self, on: bool);
+}
+
+/// Keyboard state and the blocking "wait for a key" opcode (`FX0A`).
+pub trait InputBackend {
+    /// Process pending input events, updating key state. Returns `false` if the frontend
+    /// has requested the emulator quit (e.g. the window was closed).
+    fn poll(&mut self) -> bool;
+    fn is_key_down(&self, key: usize) -> bool;
+    /// Block until a key is pressed, returning its CHIP-8 key index.
+    fn wait_for_key(&mut self) -> u8;
+}
+
+/// Headless no-op backend: drops frames, never beeps, and reports every key as up.
+/// `wait_for_key` does not block: it returns key 0 at once. Useful for running ROMs in
+/// tests or tools that only care about the interpreter's state.
+#[derive(Default)]
+pub struct NullBackend;
+
+impl VideoBackend for NullBackend {
+    fn draw(&mut self, _screen: &[[bool; 64]; 32]) {}
+}
+
+impl AudioBackend for NullBackend {
+    fn set_beep(&mut self, _on: bool) {}
+}
+
+impl InputBackend for NullBackend {
+    fn poll(&mut self) -> bool {
+        true
+    }
+
+    fn is_key_down(&self, _key: usize) -> bool {
+        false
+    }
+
+    fn wait_for_key(&mut self) -> u8 {
+        0
+    }
+}
@@ -0,0 +1,406 @@
+//! The CHIP-8 interpreter core. Generic over the `VideoBackend`/`AudioBackend`/`InputBackend`
+//! traits so it can be driven by a real SDL frontend or a headless `NullBackend` alike.
+
+use std::io::{self, BufRead, Write as _};
+use std::time::{Duration, Instant};
+use std::thread;
+use rand::random;
+
+use crate::backend::{AudioBackend, InputBackend, VideoBackend};
+use crate::disasm;
+use crate::quirks::Quirks;
+
+static CHARACTER_SPRITES: [u8; 80] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+];
+
+pub struct Chip8<B: VideoBackend + AudioBackend + InputBackend> {
+    // Memory and registers
+    mem: [u8; 0x1000],
+    v: [u8; 16],
+    i: u16,
+    pc: usize,
+    stack: Vec<usize>,
+    // Timers
+    delay_timer: u32,
+    sound_timer: u32,
+    // Screen
+    screen: [[bool; 64]; 32],
+    // Backend
+    backend: B,
+    quirks: Quirks,
+
+    running: bool,
+    pub debug: bool,
+    /// `step()` calls to execute per 60 Hz frame. Defaults to 10 (~600 Hz), tunable via
+    /// `--cycles-per-frame`/`--ips` so game speed isn't tied to render/timer rate.
+    pub cycles_per_frame: usize,
+}
+
+/// Default CPU cycles executed per 60 Hz frame (~600 Hz), a common middle ground across
+/// CHIP-8 ROMs that assume varying clock speeds.
+const DEFAULT_CYCLES_PER_FRAME: usize = 10;
+
+/// Maximum number of frames `run()` will try to catch up on after a stall, before it just
+/// resumes real-time pacing from wherever it left off.
+const MAX_CATCHUP_FRAMES: u32 = 5;
+
+impl<B: VideoBackend + AudioBackend + InputBackend> Chip8<B> {
+
+    pub fn load(filename: &str, backend: B, quirks: Quirks) -> Chip8<B> {
+        let data = std::fs::read(filename).expect("Error reading chip8 rom file");
+        Chip8::from_rom(&data, backend, quirks)
+    }
+
+    /// Build a `Chip8` directly from ROM bytes, skipping the filesystem. Used by `load()`
+    /// and by tests that want to drive the core headlessly without a ROM file on disk.
+    pub fn from_rom(rom: &[u8], backend: B, quirks: Quirks) -> Chip8<B> {
+        let mut mem = [0; 0x1000];
+        // Copy rom into Chip8 memory
+        mem[0..80].copy_from_slice(&CHARACTER_SPRITES);
+        mem[512..(512 + rom.len())].copy_from_slice(rom);
+
+        Chip8 {
+            mem,
+            v: [0; 16],
+            i: 0,
+            pc: 0x200,
+            stack: Vec::new(),
+            delay_timer: 0,
+            sound_timer: 0,
+            running: false,
+            screen: [[false; 64]; 32],
+            backend,
+            quirks,
+            debug: false,
+            cycles_per_frame: DEFAULT_CYCLES_PER_FRAME,
+        }
+    }
+
+    /// The current value of the 16 general-purpose registers.
+    pub fn v(&self) -> &[u8; 16] {
+        &self.v
+    }
+
+    /// The current 64x32 monochrome framebuffer.
+    pub fn screen(&self) -> &[[bool; 64]; 32] {
+        &self.screen
+    }
+
+    /// The current value of the address register `I`.
+    pub fn i(&self) -> u16 {
+        self.i
+    }
+
+    /// Execute `n` opcodes without frame pacing, timers, or rendering. Lets a headless
+    /// `Chip8<NullBackend>` be driven for a fixed number of cycles and then asserted on.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    fn play_sound(&mut self) {
+        self.backend.set_beep(self.sound_timer > 0);
+    }
+
+    fn render(&mut self) {
+        self.backend.draw(&self.screen);
+    }
+
+    fn tick_frame_timers(&mut self) {
+        if self.delay_timer > 0 { self.delay_timer -= 1 }
+        if self.sound_timer > 0 { self.sound_timer -= 1 }
+    }
+
+    fn clear_screen(&mut self) {
+        self.screen = [[false; 64]; 32];
+    }
+
+    /// Print the decoded instruction and full register/stack state, then block until the
+    /// user presses Enter. Used by `--debug` to single-step through a ROM.
+    fn debug_pause(&self, op: u16) {
+        println!("{:04X}: {:04X}  {}", self.pc, op, disasm::disassemble(op));
+        println!("  v:{:02X?}", self.v);
+        println!("  i:{:04X}  pc:{:04X}  stack:{:04X?}", self.i, self.pc, self.stack);
+        print!("  (press enter to step) ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line).unwrap();
+    }
+
+    #[allow(clippy::cognitive_complexity)]
+    fn step(&mut self) {
+        let op = ((self.mem[self.pc] as u16) << 8) | (self.mem[self.pc + 1] as u16);
+        if self.debug { self.debug_pause(op); }
+        let nnn = op & 0x0FFF;
+        let nn = (op & 0x00FF) as u8;
+        let n = op & 0x000F;
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let y = ((op & 0x00F0) >> 4) as usize;
+        match op & 0xF000 {
+            0x0000 if op & 0x00FF == 0xE0 => { self.clear_screen(); self.pc += 2 },
+            0x0000 if op & 0x00FF == 0xEE => { self.pc = self.stack.pop().unwrap() },
+            0x0000 => { panic!("RCA 1802 programs are not supported!") },
+            0x1000 => { self.pc = nnn as usize },
+            0x2000 => { self.stack.push(self.pc + 2); self.pc = nnn as usize }
+            0x3000 => { if self.v[x] == nn { self.pc += 4 } else { self.pc += 2 } }
+            0x4000 => { if self.v[x] != nn { self.pc += 4 } else { self.pc += 2 } }
+            0x5000 => { if self.v[x] == self.v[y] { self.pc += 4 } else { self.pc += 2 } }
+            0x6000 => { self.v[x] = nn; self.pc += 2 }
+            0x7000 => { self.v[x] = self.v[x].wrapping_add(nn); self.pc += 2 }
+            #[allow(clippy::verbose_bit_mask)]
+            0x8000 if op & 0x000F == 0 => { self.v[x] = self.v[y]; self.pc += 2 }
+            0x8000 if op & 0x000F == 1 => {
+                self.v[x] |= self.v[y];
+                if self.quirks.vf_reset_on_logic { self.v[0xF] = 0 }
+                self.pc += 2;
+            }
+            0x8000 if op & 0x000F == 2 => {
+                self.v[x] &= self.v[y];
+                if self.quirks.vf_reset_on_logic { self.v[0xF] = 0 }
+                self.pc += 2;
+            }
+            0x8000 if op & 0x000F == 3 => {
+                self.v[x] ^= self.v[y];
+                if self.quirks.vf_reset_on_logic { self.v[0xF] = 0 }
+                self.pc += 2;
+            }
+            0x8000 if op & 0x000F == 4 => {
+                let res = self.v[x] as u16 + self.v[y] as u16;
+                self.v[0xF] = if res > 255 { 1 } else { 0 };
+                self.v[x] = res as u8;
+                self.pc += 2;
+            }
+            0x8000 if op & 0x000F == 5 => {
+                let res = self.v[x] as i16 - self.v[y] as i16;
+                self.v[0xF] = (res >= 0) as u8;
+                self.v[x] = res as u8;
+                self.pc += 2;
+            }
+            0x8000 if op & 0x000F == 6 => {
+                let src = if self.quirks.shift_uses_vy { self.v[y] } else { self.v[x] };
+                self.v[0xF] = src & 0x1;
+                self.v[x] = src >> 1;
+                self.pc += 2;
+            }
+            0x8000 if op & 0x000F == 7 => {
+                let res = self.v[y] as i16 - self.v[x] as i16;
+                self.v[0xF] = (res >= 0) as u8;
+                self.v[x] = res as u8;
+                self.pc += 2;
+            }
+            0x8000 if op & 0x000F == 0xE => {
+                let src = if self.quirks.shift_uses_vy { self.v[y] } else { self.v[x] };
+                self.v[0xF] = (src & 0x80) >> 7;
+                self.v[x] = src << 1;
+                self.pc += 2;
+            }
+            0x9000 => { if self.v[x] != self.v[y] { self.pc += 4 } else { self.pc += 2 } }
+            0xA000 => { self.i = nnn; self.pc += 2 }
+            0xB000 => {
+                self.pc = if self.quirks.jump_with_vx {
+                    self.v[x] as usize + nnn as usize
+                } else {
+                    self.v[0] as usize + nnn as usize
+                };
+            }
+            0xC000 => { self.v[x] = random::<u8>() & nn; self.pc += 2 }
+            0xD000 => {
+                let mut collision = false;
+                let ypos = self.v[y] as usize;
+                let xpos = self.v[x] as usize;
+                for sy in (0..n as usize).map(|y| y + ypos) {
+                    let wy = if sy >= 32 { sy - 32 } else { sy };
+                    for sx in (0..8).map(|x| x + xpos) {
+                        let wx = if sx >= 64 { sx - 64 } else { sx };
+                        if (self.mem[self.i as usize + (sy - ypos)] & (0x80 >> (sx - xpos))) != 0 {
+                            if self.screen[wy][wx] { self.screen[wy][wx] = false; collision = true } else { self.screen[wy][wx] = true }
+                        }
+                    }
+                }
+                self.v[0xF] = collision as u8;
+                self.pc += 2;
+            }
+            0xE000 if op & 0x00FF == 0x9E => { if self.backend.is_key_down(self.v[x] as usize) { self.pc += 4} else { self.pc += 2} }
+            0xE000 if op & 0x00FF == 0xA1 => { if !self.backend.is_key_down(self.v[x] as usize) { self.pc += 4} else { self.pc += 2} }
+            0xF000 if op & 0x00FF == 0x07 => { self.v[x] = self.delay_timer as u8; self.pc += 2 }
+            0xF000 if op & 0x00FF == 0x0A => { self.v[x] = self.backend.wait_for_key(); self.pc += 2 }
+            0xF000 if op & 0x00FF == 0x15 => { self.delay_timer = self.v[x] as u32; self.pc +=2 }
+            0xF000 if op & 0x00FF == 0x18 => { self.sound_timer = self.v[x] as u32; self.pc +=2 }
+            0xF000 if op & 0x00FF == 0x1E => {
+                self.i += self.v[x as usize] as u16;
+                if self.i > 0xFFF { self.v[0xF] = 1} else { self.v[0xF] = 0 };
+                self.pc += 2;
+            }
+            0xF000 if op & 0x00FF == 0x29 => { self.i = 5 * self.v[x] as u16; self.pc += 2 }
+            0xF000 if op & 0x00FF == 0x33 => {
+                self.mem[self.i as usize] = (self.v[x] / 100) as u8;
+                self.mem[self.i as usize + 1] = ((self.v[x] % 100) / 10) as u8;
+                self.mem[self.i as usize + 2] = (self.v[x] % 10) as u8;
+                self.pc += 2;
+            }
+            0xF000 if op & 0x00FF == 0x55 => {
+                let count = if self.quirks.inclusive_register_range { x + 1 } else { x };
+                for o in 0..count { self.mem[self.i as usize + o] = self.v[o] }
+                if self.quirks.load_store_increments_i { self.i += count as u16 }
+                self.pc += 2;
+            }
+            0xF000 if op & 0x00FF == 0x65 => {
+                let count = if self.quirks.inclusive_register_range { x + 1 } else { x };
+                for o in 0..count { self.v[o] = self.mem[self.i as usize + o] }
+                if self.quirks.load_store_increments_i { self.i += count as u16 }
+                self.pc += 2;
+            }
+            _ => { panic!("Unsupported opcode: {:04X}", op) }
+        }
+    }
+
+    /// Drive a fixed 60 Hz frame via an accumulator, rather than tying timers and
+    /// rendering to however fast `step()` happens to run. Each frame executes
+    /// `cycles_per_frame` instructions, decrements both timers exactly once, and
+    /// polls/renders exactly once — independent of `cycles_per_frame`.
+    pub fn run(&mut self) {
+        self.running = true;
+        let frame_dur = Duration::from_secs_f64(1.0 / 60.0);
+        // Caps how many frames a single stall (resize/drag, a debugger breakpoint, OS
+        // suspend) can make us "catch up" on, so a stall causes a visible slowdown
+        // instead of a burst of instantly-replayed frames once control returns.
+        let max_accumulator = frame_dur * MAX_CATCHUP_FRAMES;
+        let mut accumulator = Duration::from_secs(0);
+        let mut last_tick = Instant::now();
+        while self.running {
+            let now = Instant::now();
+            accumulator = (accumulator + (now - last_tick)).min(max_accumulator);
+            last_tick = now;
+
+            while accumulator >= frame_dur {
+                self.running = self.backend.poll();
+                self.tick_frame_timers();
+                self.play_sound();
+                for _ in 0..self.cycles_per_frame {
+                    self.step();
+                }
+                self.render();
+                accumulator -= frame_dur;
+            }
+
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::NullBackend;
+
+    #[test]
+    fn step_n_executes_arithmetic_opcodes() {
+        // 6005: LD V0, 0x05 ; 6103: LD V1, 0x03 ; 8014: ADD V0, V1
+        let rom = [0x60, 0x05, 0x61, 0x03, 0x80, 0x14];
+        let mut chip8 = Chip8::from_rom(&rom, NullBackend, Quirks::default());
+
+        chip8.step_n(3);
+
+        assert_eq!(chip8.v()[0], 8);
+        assert_eq!(chip8.v()[1], 3);
+    }
+
+    #[test]
+    fn step_n_draws_a_sprite_into_the_screen() {
+        // A208: LD I, 0x208 ; 6000: LD V0, 0 ; 6100: LD V1, 0 ; D012: DRW V0, V1, 2
+        // followed by two sprite rows (0xFF solid, 0x00 blank) at 0x208.
+        let rom = [0xA2, 0x08, 0x60, 0x00, 0x61, 0x00, 0xD0, 0x12, 0xFF, 0x00];
+        let mut chip8 = Chip8::from_rom(&rom, NullBackend, Quirks::default());
+
+        chip8.step_n(4);
+
+        assert!(chip8.screen()[0][0]);
+        assert!(chip8.screen()[0][7]);
+        assert!(!chip8.screen()[0][8]);
+        assert!(!chip8.screen()[1][0]);
+    }
+
+    #[test]
+    fn shift_uses_vy_shifts_vy_into_vx() {
+        // 6001: LD V0, 1 ; 6102: LD V1, 2 ; 8016: SHR V0 {, V1}
+        let rom = [0x60, 0x01, 0x61, 0x02, 0x80, 0x16];
+        let quirks = Quirks { shift_uses_vy: true, ..Quirks::default() };
+        let mut chip8 = Chip8::from_rom(&rom, NullBackend, quirks);
+
+        chip8.step_n(3);
+
+        // Shifted V1 (2 >> 1 = 1), not V0 (1 >> 1 would have been 0).
+        assert_eq!(chip8.v()[0], 1);
+        assert_eq!(chip8.v()[0xF], 0);
+    }
+
+    #[test]
+    fn exclusive_register_range_leaves_the_last_register_unloaded() {
+        // 6100: LD V1, 0 ; A206: LD I, 0x206 ; F165: LD V0..V1, [I] ; AA BB: data bytes
+        let rom = [0x61, 0x00, 0xA2, 0x06, 0xF1, 0x65, 0xAA, 0xBB];
+        let quirks = Quirks { inclusive_register_range: false, ..Quirks::default() };
+        let mut chip8 = Chip8::from_rom(&rom, NullBackend, quirks);
+
+        chip8.step_n(3);
+
+        assert_eq!(chip8.v()[0], 0xAA);
+        assert_eq!(chip8.v()[1], 0x00);
+    }
+
+    #[test]
+    fn load_store_increments_i_disabled_leaves_i_unchanged() {
+        // 6005: LD V0, 5 ; A300: LD I, 0x300 ; F055: LD [I], V0
+        let rom = [0x60, 0x05, 0xA3, 0x00, 0xF0, 0x55];
+        let quirks = Quirks { load_store_increments_i: false, ..Quirks::default() };
+        let mut chip8 = Chip8::from_rom(&rom, NullBackend, quirks);
+
+        chip8.step_n(3);
+
+        assert_eq!(chip8.i(), 0x300);
+    }
+
+    #[test]
+    fn jump_with_vx_uses_the_register_named_in_the_instruction() {
+        // 6000: LD V0, 0 ; 6210: LD V2, 0x10 ; B210: JP V2, 0x210 ; ... ; 6399: LD V3, 0x99
+        let mut rom = vec![0x60, 0x00, 0x62, 0x10, 0xB2, 0x10];
+        rom.extend(std::iter::repeat(0u8).take(26));
+        rom.extend([0x63, 0x99]);
+        let quirks = Quirks { jump_with_vx: true, ..Quirks::default() };
+        let mut chip8 = Chip8::from_rom(&rom, NullBackend, quirks);
+
+        // Jumps to V2 (0x10) + 0x210 = 0x220, not V0 (0) + 0x210.
+        chip8.step_n(4);
+
+        assert_eq!(chip8.v()[3], 0x99);
+    }
+
+    #[test]
+    fn vf_reset_on_logic_zeroes_vf_after_or() {
+        // 60FF: LD V0, 0xFF ; 6101: LD V1, 1 ; 8014: ADD V0, V1 (overflows, VF=1)
+        // 6205: LD V2, 5 ; 6303: LD V3, 3 ; 8231: OR V2, V3
+        let rom = [0x60, 0xFF, 0x61, 0x01, 0x80, 0x14, 0x62, 0x05, 0x63, 0x03, 0x82, 0x31];
+        let quirks = Quirks { vf_reset_on_logic: true, ..Quirks::default() };
+        let mut chip8 = Chip8::from_rom(&rom, NullBackend, quirks);
+
+        chip8.step_n(6);
+
+        assert_eq!(chip8.v()[0xF], 0);
+    }
+}
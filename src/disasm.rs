@@ -0,0 +1,66 @@
+//! Decodes raw CHIP-8 opcodes into human-readable mnemonics, used by the
+//! `--disasm` listing mode and the `--debug` step-debugger.
+
+/// Decode a single 16-bit opcode into its mnemonic form, e.g. `0x2202` -> `CALL 0x202`.
+pub fn disassemble(op: u16) -> String {
+    let nnn = op & 0x0FFF;
+    let nn = (op & 0x00FF) as u8;
+    let n = op & 0x000F;
+    let x = (op & 0x0F00) >> 8;
+    let y = (op & 0x00F0) >> 4;
+
+    match op & 0xF000 {
+        0x0000 if op == 0x00E0 => "CLS".to_string(),
+        0x0000 if op == 0x00EE => "RET".to_string(),
+        0x0000 => format!("SYS 0x{:03X}", nnn),
+        0x1000 => format!("JP 0x{:03X}", nnn),
+        0x2000 => format!("CALL 0x{:03X}", nnn),
+        0x3000 => format!("SE V{:X}, 0x{:02X}", x, nn),
+        0x4000 => format!("SNE V{:X}, 0x{:02X}", x, nn),
+        0x5000 => format!("SE V{:X}, V{:X}", x, y),
+        0x6000 => format!("LD V{:X}, 0x{:02X}", x, nn),
+        0x7000 => format!("ADD V{:X}, 0x{:02X}", x, nn),
+        0x8000 if n == 0x0 => format!("LD V{:X}, V{:X}", x, y),
+        0x8000 if n == 0x1 => format!("OR V{:X}, V{:X}", x, y),
+        0x8000 if n == 0x2 => format!("AND V{:X}, V{:X}", x, y),
+        0x8000 if n == 0x3 => format!("XOR V{:X}, V{:X}", x, y),
+        0x8000 if n == 0x4 => format!("ADD V{:X}, V{:X}", x, y),
+        0x8000 if n == 0x5 => format!("SUB V{:X}, V{:X}", x, y),
+        0x8000 if n == 0x6 => format!("SHR V{:X}", x),
+        0x8000 if n == 0x7 => format!("SUBN V{:X}, V{:X}", x, y),
+        0x8000 if n == 0xE => format!("SHL V{:X}", x),
+        0x9000 => format!("SNE V{:X}, V{:X}", x, y),
+        0xA000 => format!("LD I, 0x{:03X}", nnn),
+        0xB000 => format!("JP V0, 0x{:03X}", nnn),
+        0xC000 => format!("RND V{:X}, 0x{:02X}", x, nn),
+        0xD000 => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        0xE000 if nn == 0x9E => format!("SKP V{:X}", x),
+        0xE000 if nn == 0xA1 => format!("SKNP V{:X}", x),
+        0xF000 if nn == 0x07 => format!("LD V{:X}, DT", x),
+        0xF000 if nn == 0x0A => format!("LD V{:X}, K", x),
+        0xF000 if nn == 0x15 => format!("LD DT, V{:X}", x),
+        0xF000 if nn == 0x18 => format!("LD ST, V{:X}", x),
+        0xF000 if nn == 0x1E => format!("ADD I, V{:X}", x),
+        0xF000 if nn == 0x29 => format!("LD F, V{:X}", x),
+        0xF000 if nn == 0x33 => format!("LD B, V{:X}", x),
+        0xF000 if nn == 0x55 => format!("LD [I], V{:X}", x),
+        0xF000 if nn == 0x65 => format!("LD V{:X}, [I]", x),
+        _ => format!("DATA 0x{:04X}", op),
+    }
+}
+
+/// Print a full listing of a ROM image, one mnemonic per 16-bit word, starting at 0x200
+/// (the conventional CHIP-8 program load address).
+pub fn print_listing(rom: &[u8]) {
+    let mut addr = 0x200;
+    let mut i = 0;
+    while i + 1 < rom.len() {
+        let op = ((rom[i] as u16) << 8) | (rom[i + 1] as u16);
+        println!("{:04X}: {:04X}  {}", addr, op, disassemble(op));
+        addr += 2;
+        i += 2;
+    }
+    if i < rom.len() {
+        println!("{:04X}: {:02X}..  DATA", addr, rom[i]);
+    }
+}
@@ -0,0 +1,63 @@
+//! CHIP-8 variants disagree on the exact semantics of a handful of opcodes. `Quirks` makes
+//! that a runtime choice instead of a hard-coded one, so the same interpreter can run
+//! original COSMAC VIP programs and SUPER-CHIP-era games without mis-executing either.
+
+use std::fs;
+
+/// Toggles for opcode behavior that differs between CHIP-8 implementations. The `Default`
+/// impl matches the modern/XO-CHIP consensus used by most community test ROMs.
+#[derive(Debug, Clone, Copy)]
+pub struct Quirks {
+    /// `8XY6`/`8XYE` shift `Vy` into `Vx` instead of shifting `Vx` in place.
+    pub shift_uses_vy: bool,
+    /// `FX55`/`FX65` advance `I` past the last register saved/loaded instead of leaving it unchanged.
+    pub load_store_increments_i: bool,
+    /// `FX55`/`FX65` cover `V0..=Vx` instead of skipping the last register (`V0..Vx`).
+    pub inclusive_register_range: bool,
+    /// `BNNN` jumps to `XNN + Vx` (the register named by the top nibble) instead of `NNN + V0`.
+    pub jump_with_vx: bool,
+    /// `8XY1`/`8XY2`/`8XY3` reset `VF` to 0 after the logic op, as the original COSMAC VIP did.
+    pub vf_reset_on_logic: bool,
+}
+
+impl Default for Quirks {
+    fn default() -> Quirks {
+        Quirks {
+            shift_uses_vy: false,
+            load_store_increments_i: true,
+            inclusive_register_range: true,
+            jump_with_vx: false,
+            vf_reset_on_logic: false,
+        }
+    }
+}
+
+impl Quirks {
+    /// Parse a simple `key = true|false` profile, one setting per line, `#` for comments.
+    /// Unrecognized keys are ignored so profiles can be shared across interpreter versions.
+    pub fn from_file(path: &str) -> Quirks {
+        let text = fs::read_to_string(path).expect("Error reading quirks profile");
+        let mut quirks = Quirks::default();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap().trim();
+            let value = match parts.next() {
+                Some(v) => v.trim() == "true",
+                None => continue,
+            };
+            match key {
+                "shift_uses_vy" => quirks.shift_uses_vy = value,
+                "load_store_increments_i" => quirks.load_store_increments_i = value,
+                "inclusive_register_range" => quirks.inclusive_register_range = value,
+                "jump_with_vx" => quirks.jump_with_vx = value,
+                "vf_reset_on_logic" => quirks.vf_reset_on_logic = value,
+                _ => {}
+            }
+        }
+        quirks
+    }
+}
@@ -0,0 +1,166 @@
+//! The real frontend: renders through SDL2, reads the keyboard through SDL2's event pump,
+//! and beeps through an `AudioCallback` device. Implements all three backend traits so
+//! `Chip8` never has to know SDL exists.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use sdl2::audio::{AudioDevice, AudioSpecDesired};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use sdl2::EventPump;
+use std::thread;
+use std::time::Duration;
+
+use crate::audio::SquareWave;
+use crate::backend::{AudioBackend, InputBackend, VideoBackend};
+
+static SCALE: u32 = 10;
+static FOREGROUND: [u8; 3] = [255, 255, 255];
+static BACKGROUND: [u8; 3] = [0, 0, 0];
+
+pub struct SdlBackend {
+    canvas: Canvas<Window>,
+    texture: Texture<'static>,
+    event_pump: EventPump,
+    audio: AudioDevice<SquareWave>,
+    beeping: Arc<AtomicBool>,
+    keys_pressed: [bool; 16],
+    quit: bool,
+}
+
+impl SdlBackend {
+    pub fn new(beep_freq: f32) -> SdlBackend {
+        let sdl_context = sdl2::init().unwrap();
+        let video_subsystem = sdl_context.video().unwrap();
+        let window = video_subsystem.window("StackChip8", 64 * SCALE, 32 * SCALE)
+            .position_centered()
+            .resizable()
+            .build().unwrap();
+        let canvas = window.into_canvas().build().unwrap();
+        // Leaked once for the program's lifetime: the texture borrows from it, and the
+        // texture needs to live as long as the backend itself.
+        let texture_creator: &'static TextureCreator<WindowContext> =
+            Box::leak(Box::new(canvas.texture_creator()));
+        let texture = texture_creator
+            .create_texture_streaming(PixelFormatEnum::RGB24, 64, 32)
+            .unwrap();
+        let event_pump = sdl_context.event_pump().unwrap();
+
+        let audio_spec = AudioSpecDesired {
+            freq: Some(48_000),
+            channels: Some(1),
+            samples: Some(2048)
+        };
+        let beeping = Arc::new(AtomicBool::new(false));
+        let audio_subsystem = sdl_context.audio().unwrap();
+        let audio = audio_subsystem.open_playback(None, &audio_spec, |spec| {
+            SquareWave {
+                phase: 0.0,
+                freq: beep_freq,
+                sample_rate: spec.freq as f32,
+                volume: 1_000,
+                beeping: beeping.clone(),
+            }
+        }).unwrap();
+        audio.resume();
+
+        SdlBackend {
+            canvas, texture, event_pump, audio, beeping,
+            keys_pressed: [false; 16],
+            quit: false,
+        }
+    }
+
+    fn match_keycode_to_key(keycode: Keycode) -> Option<usize> {
+        match keycode {
+            Keycode::Num1 => Some(1),
+            Keycode::Num2 => Some(2),
+            Keycode::Num3 => Some(3),
+            Keycode::Num4 => Some(12),
+            Keycode::Q => Some(4),
+            Keycode::W => Some(5),
+            Keycode::E => Some(6),
+            Keycode::R => Some(13),
+            Keycode::A => Some(7),
+            Keycode::S => Some(8),
+            Keycode::D => Some(9),
+            Keycode::F => Some(14),
+            Keycode::Z => Some(10),
+            Keycode::X => Some(0),
+            Keycode::C => Some(11),
+            Keycode::V => Some(15),
+            _ => None
+        }
+    }
+}
+
+impl VideoBackend for SdlBackend {
+    fn draw(&mut self, screen: &[[bool; 64]; 32]) {
+        let mut buf = [0u8; 64 * 32 * 3];
+        for (y, row) in screen.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                let color = if *pixel { &FOREGROUND } else { &BACKGROUND };
+                let offset = (y * 64 + x) * 3;
+                buf[offset..offset + 3].copy_from_slice(color);
+            }
+        }
+        self.texture.update(None, &buf, 64 * 3).unwrap();
+
+        self.canvas.set_draw_color(Color::RGB(BACKGROUND[0], BACKGROUND[1], BACKGROUND[2]));
+        self.canvas.clear();
+        self.canvas.copy(&self.texture, None, None).unwrap();
+        self.canvas.present();
+    }
+}
+
+impl AudioBackend for SdlBackend {
+    fn set_beep(&mut self, on: bool) {
+        self.beeping.store(on, Ordering::Relaxed);
+    }
+}
+
+impl InputBackend for SdlBackend {
+    fn poll(&mut self) -> bool {
+        for event in self.event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } => self.quit = true,
+                Event::KeyDown { keycode: Some(k), .. } => {
+                    if let Some(i) = SdlBackend::match_keycode_to_key(k) { self.keys_pressed[i] = true }
+                },
+                Event::KeyUp { keycode: Some(k), .. } => {
+                    if let Some(i) = SdlBackend::match_keycode_to_key(k) { self.keys_pressed[i] = false }
+                },
+                _ => {}
+            }
+        }
+        !self.quit
+    }
+
+    fn is_key_down(&self, key: usize) -> bool {
+        self.keys_pressed[key]
+    }
+
+    fn wait_for_key(&mut self) -> u8 {
+        loop {
+            for event in self.event_pump.poll_iter() {
+                match event {
+                    Event::KeyDown { keycode: Some(k), .. } => {
+                        if let Some(i) = SdlBackend::match_keycode_to_key(k) {
+                            self.keys_pressed[i] = true;
+                            return i as u8;
+                        }
+                    },
+                    Event::KeyUp { keycode: Some(k), .. } => {
+                        if let Some(i) = SdlBackend::match_keycode_to_key(k) { self.keys_pressed[i] = false }
+                    },
+                    Event::Quit { .. } => self.quit = true,
+                    _ => {}
+                }
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+    }
+}